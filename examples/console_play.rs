@@ -1,30 +1,57 @@
+use std::env;
+use std::thread::sleep;
+use std::time::Duration;
+
 use console::Term;
 
-use rust_snake::{Direction, SnakeGame};
+use rust_snake::{Direction, GameState, SnakeGame};
 use text_io::{read, try_read, try_scan};
 
 fn main() {
     let terminal = Term::stdout();
+    let auto = env::args().any(|arg| arg == "--auto");
 
-    let mut game = SnakeGame::new(7, 7, 3);
+    let mut game = SnakeGame::new(7, 7, 3, 20);
 
     println!("{}", game);
 
-    loop {
-        let str_in: String = read!();
-
-        for i in str_in.chars() {
-            let dir = match i {
-                'w' => Some(Direction::UP),
-                'a' => Some(Direction::LEFT),
-                's' => Some(Direction::DOWN),
-                'd' => Some(Direction::RIGHT),
-                _ => None,
-            };
-            game.tick(dir);
+    let mut state = GameState::Running;
+    if auto {
+        while state == GameState::Running {
+            let dir = game.suggest_direction();
+            state = game.tick(dir);
+
+            terminal.clear_screen().unwrap();
+            println!("{}", game);
+            sleep(Duration::from_millis(150));
         }
+    } else {
+        loop {
+            let str_in: String = read!();
+
+            for i in str_in.chars() {
+                let dir = match i {
+                    'w' => Some(Direction::UP),
+                    'a' => Some(Direction::LEFT),
+                    's' => Some(Direction::DOWN),
+                    'd' => Some(Direction::RIGHT),
+                    _ => None,
+                };
+                state = game.tick(dir);
+            }
+
+            terminal.clear_screen().unwrap();
+            println!("{}", game);
+
+            if state != GameState::Running {
+                break;
+            }
+        }
+    }
 
-        terminal.clear_screen().unwrap();
-        println!("{}", game);
+    match game.state() {
+        GameState::Won => println!("You won! Final score: {}", game.score()),
+        GameState::GameOver => println!("Game over! Final score: {}", game.score()),
+        GameState::Running => unreachable!(),
     }
 }
@@ -0,0 +1,1027 @@
+use ndarray::{Array2, Axis};
+use rand::prelude::ThreadRng;
+use rand::{thread_rng, Rng};
+use serde::de::Error as SerdeError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Error, Formatter, Write};
+
+use crate::{Direction, GameState};
+
+/// sane upper bound on a posted `ArenaState`'s board size, so an untrusted `width`/`height`
+/// can't overflow `width * height` or blow up `Array2::from_elem`'s allocation
+const MAX_BOARD_CELLS: usize = 1_000_000;
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::UP,
+    Direction::DOWN,
+    Direction::LEFT,
+    Direction::RIGHT,
+];
+
+/// one competitor in a `SnakeArena`: its own head, direction, length and body
+#[readonly::make]
+#[derive(Debug, Clone)]
+pub struct Snake {
+    pub pos: (usize, usize), // (y, x) due to array index notation, head of snake
+    pub dir: Option<Direction>,
+    pub lvl: u32, // determine how long snake part persist (increase when eat food)
+    /// ordered occupied cells, head at the back
+    pub body: VecDeque<(usize, usize)>,
+    pub state: GameState,
+}
+
+impl Snake {
+    fn new(pos: (usize, usize), lvl: u32) -> Self {
+        Snake {
+            pos,
+            dir: None,
+            lvl,
+            body: VecDeque::new(),
+            state: GameState::Running,
+        }
+    }
+
+    // set snake direction of motion
+    fn set_direction(&mut self, dir: Direction) {
+        match self.dir {
+            Some(current) if dir == current.opposite() => {}
+            _ => self.dir = Some(dir),
+        }
+    }
+}
+
+/// builds a Hamiltonian cycle over a `height` x `width` grid (the "perfect player" trick:
+/// following it forever never collides), returning each cell's position along the cycle.
+///
+/// requires `height` to be even (a grid graph only has a Hamiltonian cycle at all when at
+/// least one of its dimensions is even, since it is bipartite); reserves column 0 as a
+/// vertical return corridor and snakes back and forth through the remaining columns row by
+/// row, closing the loop back up column 0.
+fn hamiltonian_cycle_even_height(width: usize, height: usize) -> Array2<usize> {
+    debug_assert!(width >= 2 && height >= 2 && height.is_multiple_of(2));
+
+    let mut order = Vec::with_capacity(width * height);
+
+    order.push((0, 0));
+    for row in 0..height {
+        let cols: Box<dyn Iterator<Item = usize>> = if row % 2 == 0 {
+            Box::new(1..width)
+        } else {
+            Box::new((1..width).rev())
+        };
+        order.extend(cols.map(|col| (row, col)));
+    }
+    order.extend((1..height).rev().map(|row| (row, 0)));
+
+    let mut cycle_index = Array2::zeros((height, width));
+    for (index, &pos) in order.iter().enumerate() {
+        cycle_index[pos] = index;
+    }
+    cycle_index
+}
+
+/// builds a Hamiltonian cycle over a `height` x `width` grid, same guarantee as
+/// `hamiltonian_cycle_even_height`; works whenever `width` or `height` is even and both are at
+/// least 2 (transposing into the even-height construction when only `width` is even), and
+/// returns `None` otherwise: a grid graph with both dimensions odd admits no Hamiltonian cycle
+/// at all, and neither does one only a single cell wide or tall.
+fn hamiltonian_cycle(width: usize, height: usize) -> Option<Array2<usize>> {
+    if width < 2 || height < 2 {
+        return None;
+    }
+
+    if height.is_multiple_of(2) {
+        Some(hamiltonian_cycle_even_height(width, height))
+    } else if width.is_multiple_of(2) {
+        Some(hamiltonian_cycle_even_height(height, width).reversed_axes())
+    } else {
+        None
+    }
+}
+
+/// cell reached by moving `pos` one step in `dir` within a `board_size` (height, width) grid,
+/// wrapping around the edges
+fn wrapped_step(board_size: (usize, usize), pos: (usize, usize), dir: Direction) -> (usize, usize) {
+    match dir {
+        Direction::UP => (pos.0.checked_sub(1).unwrap_or(board_size.0 - 1), pos.1),
+        Direction::DOWN => (
+            Some(pos.0 + 1).filter(|&y| y < board_size.0).unwrap_or(0),
+            pos.1,
+        ),
+        Direction::LEFT => (pos.0, pos.1.checked_sub(1).unwrap_or(board_size.1 - 1)),
+        Direction::RIGHT => (
+            pos.0,
+            Some(pos.1 + 1).filter(|&x| x < board_size.1).unwrap_or(0),
+        ),
+    }
+}
+
+/// spreads `count` snakes down the middle column of the board, evenly spaced; on small boards
+/// with many snakes the evenly-spaced row can collide with one already taken, in which case the
+/// snake instead gets the first free cell in row-major order, so no two snakes ever start on the
+/// same cell (as long as `count` does not exceed the number of cells on the board)
+fn spawn_positions(board_size: (usize, usize), count: usize) -> Vec<(usize, usize)> {
+    let (height, width) = board_size;
+    let mut used = HashSet::new();
+    (0..count)
+        .map(|i| {
+            let preferred = (height * (i + 1) / (count + 1), width / 2);
+            if used.insert(preferred) {
+                return preferred;
+            }
+
+            (0..height)
+                .flat_map(|y| (0..width).map(move |x| (y, x)))
+                .find(|&pos| used.insert(pos))
+                .expect("count must not exceed the number of cells on the board")
+        })
+        .collect()
+}
+
+/// a shared board and food on which one or more `Snake`s compete; `SnakeGame` is a thin
+/// single-snake wrapper around an arena of size one
+#[readonly::make]
+#[derive(Clone)]
+pub struct SnakeArena<R: Rng> {
+    /// owning snake's index at each cell, `None` where the cell is empty
+    pub board: Array2<Option<usize>>,
+    pub food_pos: (usize, usize), // (y, x) due to array index notation
+    /// ticks left before the current food expires and relocates for no reward
+    pub food_timer: u32,
+    /// ticks a freshly placed food stays alive for, i.e. what `food_timer` resets to
+    pub food_time_budget: u32,
+    pub snakes: Vec<Snake>,
+    /// total time-remaining bonus banked by each snake, indexed like `snakes`
+    pub scores: Vec<u32>,
+    /// each cell's position along the perfect-player Hamiltonian cycle (see `hamiltonian_cycle`),
+    /// or `None` when the board's dimensions don't admit one
+    pub cycle_index: Option<Array2<usize>>,
+    rng: R,
+}
+
+impl SnakeArena<ThreadRng> {
+    pub fn new(
+        board_width: usize,
+        board_height: usize,
+        starting_lengths: &[u32],
+        food_time_budget: u32,
+    ) -> Self {
+        let rng = thread_rng();
+        SnakeArena::with_rng(board_width, board_height, starting_lengths, food_time_budget, rng)
+    }
+}
+
+impl<R: Rng> SnakeArena<R> {
+    pub fn with_rng(
+        board_width: usize,
+        board_height: usize,
+        starting_lengths: &[u32],
+        food_time_budget: u32,
+        mut rng: R,
+    ) -> Self {
+        let board_size = (board_height, board_width);
+
+        let snakes: Vec<Snake> = starting_lengths
+            .iter()
+            .zip(spawn_positions(board_size, starting_lengths.len()))
+            .map(|(&lvl, pos)| Snake::new(pos, lvl))
+            .collect();
+
+        let mut arena = SnakeArena {
+            board: Array2::from_elem(board_size, None),
+            food_pos: (
+                rng.gen_range(0, board_size.0),
+                rng.gen_range(0, board_size.1),
+            ),
+            food_timer: food_time_budget,
+            food_time_budget,
+            scores: vec![0; snakes.len()],
+            cycle_index: hamiltonian_cycle(board_width, board_height),
+            snakes,
+            rng,
+        };
+
+        let initial_inputs = vec![None; arena.snakes.len()];
+        arena.tick_all(&initial_inputs);
+        arena
+    }
+
+    fn random_pos(&mut self) -> (usize, usize) {
+        let board_size = self.board_size();
+        (self.rng.gen_range(0, board_size.0), self.rng.gen_range(0, board_size.1))
+    }
+
+    fn random_food_pos(&mut self) -> (usize, usize) {
+        loop {
+            let pos = self.random_pos();
+            if self.board[pos].is_none() {
+                break pos;
+            }
+        }
+    }
+
+    pub fn board_size(&self) -> (usize, usize) {
+        (self.board.len_of(Axis(0)), self.board.len_of(Axis(1)))
+    }
+
+    /// rebuilds `board` from the current snake bodies; a dead snake's cells are cleared
+    fn sync_board(&mut self) {
+        self.board.fill(None);
+        for (i, snake) in self.snakes.iter().enumerate() {
+            if snake.state != GameState::GameOver {
+                for &seg in &snake.body {
+                    self.board[seg] = Some(i);
+                }
+            }
+        }
+    }
+
+    /// Main Game Logic for every snake at once.
+    ///
+    /// `inputs[i]`: Some(dir) = change snake i's direction to dir, None = no direction change;
+    /// snakes without a corresponding entry keep their current direction. Moves every snake's
+    /// head, then resolves collisions: a snake dies if its head enters any snake's body, and in
+    /// a head-to-head on the same cell the shorter snake dies (equal lengths both die). Already
+    /// finished snakes are left untouched. Once every snake is done, this becomes a no-op that
+    /// keeps returning the terminal states (food stops ticking down and relocating too).
+    pub fn tick_all(&mut self, inputs: &[Option<Direction>]) -> Vec<GameState> {
+        if self.snakes.iter().all(|s| s.state != GameState::Running) {
+            return self.snakes.iter().map(|s| s.state).collect();
+        }
+
+        for (snake, input) in self
+            .snakes
+            .iter_mut()
+            .zip(inputs.iter().copied().chain(std::iter::repeat(None)))
+        {
+            if snake.state == GameState::Running {
+                if let Some(dir) = input {
+                    snake.set_direction(dir);
+                }
+            }
+        }
+
+        self.food_timer = self.food_timer.saturating_sub(1);
+        if self.food_timer == 0 {
+            self.food_pos = self.random_food_pos();
+            self.food_timer = self.food_time_budget;
+        }
+
+        let board_size = self.board_size();
+        let running: Vec<bool> = self
+            .snakes
+            .iter()
+            .map(|s| s.state == GameState::Running)
+            .collect();
+
+        let new_heads: Vec<(usize, usize)> = self
+            .snakes
+            .iter()
+            .map(|snake| match snake.dir {
+                Some(dir) => wrapped_step(board_size, snake.pos, dir),
+                None => snake.pos,
+            })
+            .collect();
+
+        // a snake that hasn't been given a direction yet hasn't moved at all this tick: it
+        // stays put rather than re-entering its own current (already-occupied) head cell
+        let stayed: Vec<bool> = self.snakes.iter().map(|s| s.dir.is_none()).collect();
+
+        let grows: Vec<bool> = new_heads.iter().map(|&pos| pos == self.food_pos).collect();
+
+        // body each snake would have after this move, assuming it survives
+        let new_bodies: Vec<VecDeque<(usize, usize)>> = self
+            .snakes
+            .iter()
+            .zip(&new_heads)
+            .zip(&grows)
+            .zip(&stayed)
+            .map(|(((snake, &head), &grows), &stayed)| {
+                let mut body = snake.body.clone();
+                if !stayed {
+                    body.push_back(head);
+                    if !grows && body.len() > snake.lvl as usize {
+                        body.pop_front();
+                    }
+                }
+                body
+            })
+            .collect();
+
+        let mut dies = vec![false; self.snakes.len()];
+        for i in 0..self.snakes.len() {
+            if !running[i] || stayed[i] {
+                continue;
+            }
+
+            // self-collision: head landed inside its own post-move body
+            if new_bodies[i]
+                .iter()
+                .rev()
+                .skip(1)
+                .any(|&seg| seg == new_heads[i])
+            {
+                dies[i] = true;
+                continue;
+            }
+
+            for j in 0..self.snakes.len() {
+                if i == j || !running[j] {
+                    continue;
+                }
+
+                if new_heads[i] == new_heads[j] && !stayed[j] {
+                    // head-to-head: the shorter snake dies, equal lengths both die; a snake that
+                    // stayed put is not moving into that cell, so entering it is a body hit instead
+                    if new_bodies[i].len() <= new_bodies[j].len() {
+                        dies[i] = true;
+                    }
+                } else if new_bodies[j].iter().any(|&seg| seg == new_heads[i]) {
+                    dies[i] = true;
+                }
+            }
+        }
+
+        let mut food_eaten = false;
+        for i in 0..self.snakes.len() {
+            if !running[i] {
+                continue;
+            }
+
+            if dies[i] {
+                self.snakes[i].state = GameState::GameOver;
+                continue;
+            }
+
+            self.snakes[i].pos = new_heads[i];
+            self.snakes[i].body = new_bodies[i].clone();
+
+            if grows[i] {
+                self.snakes[i].lvl += 1;
+                self.scores[i] += self.food_timer;
+                self.food_timer = self.food_time_budget;
+                food_eaten = true;
+            }
+
+            if self.snakes[i].body.len() == self.board.len() {
+                self.snakes[i].state = GameState::Won;
+            }
+        }
+
+        self.sync_board();
+
+        // respawn only after the board reflects every snake's post-move body, so food can
+        // never land on a cell a snake just moved into this same tick; skip it if that same
+        // bite just filled the board, since there's then no free cell left to respawn onto
+        if food_eaten && self.snakes.iter().any(|s| s.state == GameState::Running) {
+            self.food_pos = self.random_food_pos();
+        }
+
+        self.snakes.iter().map(|s| s.state).collect()
+    }
+
+    /// safe-move autopilot for snake `snake_idx`: among the directions that do not immediately
+    /// kill it, prefer the one on the shortest path to the food; if none can reach the food,
+    /// fall back to the one that keeps the most free area reachable, to avoid trapping it.
+    /// Returns `None` only when every direction is immediately fatal.
+    pub fn suggest_direction(&self, snake_idx: usize) -> Option<Direction> {
+        let snake = &self.snakes[snake_idx];
+        // the tail segment only vacates once the snake is fully grown into its `lvl` (see
+        // `tick_all`); while `body.len() < lvl`, the oldest segment is still mid-growth and stays
+        // occupied. This exemption only holds for `snake_idx`'s own tail: its growth and its own
+        // tail-vacate can never conflict. For any other snake we can't tell from here whether it
+        // is about to eat this same tick (which would keep its tail put), so treat its whole body
+        // as occupied rather than risk recommending a move into a cell it hasn't actually vacated.
+        let occupied: HashSet<(usize, usize)> = self
+            .snakes
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.state == GameState::Running)
+            .flat_map(|(i, s)| {
+                let skip = if i == snake_idx && s.body.len() >= s.lvl as usize {
+                    1
+                } else {
+                    0
+                };
+                s.body.iter().copied().skip(skip)
+            })
+            .collect();
+
+        let safe: Vec<(Direction, (usize, usize))> = DIRECTIONS
+            .iter()
+            .copied()
+            .filter(|&dir| snake.dir.is_none_or(|cur| dir != cur.opposite()))
+            .map(|dir| (dir, wrapped_step(self.board_size(), snake.pos, dir)))
+            .filter(|(_, pos)| !occupied.contains(pos))
+            .collect();
+
+        if let Some((dir, _)) = safe
+            .iter()
+            .filter_map(|&(dir, pos)| {
+                self.bfs_distances(pos, &occupied)
+                    .get(&self.food_pos)
+                    .map(|&dist| (dir, dist))
+            })
+            .min_by_key(|&(_, dist)| dist)
+        {
+            return Some(dir);
+        }
+
+        safe.iter()
+            .map(|&(dir, pos)| (dir, self.bfs_distances(pos, &occupied).len()))
+            .max_by_key(|&(_, area)| area)
+            .map(|(dir, _)| dir)
+    }
+
+    /// direction that advances snake `snake_idx`'s head one step along the precomputed
+    /// Hamiltonian cycle, guaranteeing it never dies; `None` when the board doesn't admit one
+    /// (see `hamiltonian_cycle`)
+    pub fn hamiltonian_next(&self, snake_idx: usize) -> Option<Direction> {
+        let cycle_index = self.cycle_index.as_ref()?;
+        let pos = self.snakes[snake_idx].pos;
+        let next_index = (cycle_index[pos] + 1) % cycle_index.len();
+        let board_size = self.board_size();
+
+        DIRECTIONS
+            .iter()
+            .copied()
+            .find(|&dir| cycle_index[wrapped_step(board_size, pos, dir)] == next_index)
+    }
+
+    /// like `hamiltonian_next`, but takes a shortcut toward the food when it is provably
+    /// safe to do so: a candidate step may not land on any snake's body, may not pass the
+    /// tail's position along the cycle (so the snake can never lap itself), and may not
+    /// overshoot the food. Falls back to the nearest safe step along the cycle when no such
+    /// shortcut exists.
+    ///
+    /// every candidate, shortcut or not, is checked against the live occupied set and against
+    /// how far ahead of the *current* tail it lands, freshly on every call: a shortcut taken on
+    /// an earlier tick leaves the body non-contiguous in cycle-index space, so even the plain
+    /// "next cell along the cycle" can no longer be trusted blindly the way `hamiltonian_next`
+    /// trusts it.
+    pub fn hamiltonian_next_with_shortcut(&self, snake_idx: usize) -> Option<Direction> {
+        let cycle_index = self.cycle_index.as_ref()?;
+        let cycle_len = cycle_index.len();
+        let board_size = self.board_size();
+        let snake = &self.snakes[snake_idx];
+        let current = cycle_index[snake.pos];
+        let tail_index = snake.body.front().map_or(current, |&pos| cycle_index[pos]);
+
+        // how many steps ahead of the head (along the cycle) a given index is
+        let ahead_of_head = |index: usize| (index + cycle_len - current) % cycle_len;
+        let tail_ahead = ahead_of_head(tail_index);
+        let food_ahead = ahead_of_head(cycle_index[self.food_pos]);
+
+        let occupied: HashSet<(usize, usize)> = self
+            .snakes
+            .iter()
+            .filter(|s| s.state == GameState::Running)
+            .flat_map(|s| s.body.iter().copied())
+            .collect();
+
+        let candidates: Vec<(Direction, usize)> = DIRECTIONS
+            .iter()
+            .copied()
+            .map(|dir| (dir, wrapped_step(board_size, snake.pos, dir)))
+            .filter(|&(_, pos)| !occupied.contains(&pos))
+            .filter_map(|(dir, pos)| {
+                let pos_ahead = ahead_of_head(cycle_index[pos]);
+                (pos_ahead > 0 && pos_ahead < tail_ahead).then_some((dir, pos_ahead))
+            })
+            .collect();
+
+        if let Some((dir, _)) = candidates
+            .iter()
+            .copied()
+            .filter(|&(_, pos_ahead)| pos_ahead <= food_ahead)
+            .max_by_key(|&(_, pos_ahead)| pos_ahead)
+            .or_else(|| candidates.iter().copied().min_by_key(|&(_, pos_ahead)| pos_ahead))
+        {
+            return Some(dir);
+        }
+
+        // no shortcut candidate exists (e.g. `tail_ahead` is 0/1 right after spawn, before the
+        // snake has grown): fall back to the plain cycle-walk, re-validated against the live
+        // occupied set since a shortcut taken on an earlier tick can leave the body
+        // non-contiguous in cycle-index space
+        let next_index = (current + 1) % cycle_len;
+        DIRECTIONS
+            .iter()
+            .copied()
+            .find(|&dir| {
+                let pos = wrapped_step(board_size, snake.pos, dir);
+                cycle_index[pos] == next_index && !occupied.contains(&pos)
+            })
+    }
+
+    /// shortest distance in steps from `from` to every free cell, via BFS over cells not in
+    /// `occupied`
+    fn bfs_distances(
+        &self,
+        from: (usize, usize),
+        occupied: &HashSet<(usize, usize)>,
+    ) -> HashMap<(usize, usize), usize> {
+        let board_size = self.board_size();
+        let mut distances = HashMap::new();
+        distances.insert(from, 0usize);
+
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+
+        while let Some(pos) = queue.pop_front() {
+            let dist = distances[&pos];
+            for &dir in &DIRECTIONS {
+                let next = wrapped_step(board_size, pos, dir);
+                if occupied.contains(&next) || distances.contains_key(&next) {
+                    continue;
+                }
+                distances.insert(next, dist + 1);
+                queue.push_back(next);
+            }
+        }
+
+        distances
+    }
+}
+
+fn snake_glyph(index: usize, is_head: bool) -> char {
+    const HEADS: &[char] = &['@', '%', '&', '$', '+', '*'];
+    const BODIES: &[char] = &['#', 'o', 'x', '=', '~', '.'];
+    let table = if is_head { HEADS } else { BODIES };
+    table[index % table.len()]
+}
+
+impl<R: Rng> Display for SnakeArena<R> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        writeln!(f, "{}", "-".repeat(self.board.len_of(Axis(0))))?;
+
+        for (ax0, row) in self.board.axis_iter(Axis(0)).enumerate() {
+            for (ax1, cell) in row.iter().enumerate() {
+                match *cell {
+                    Some(i) => {
+                        let is_head = self.snakes[i].pos == (ax0, ax1);
+                        f.write_char(snake_glyph(i, is_head))?
+                    }
+                    None if (ax0, ax1) == self.food_pos => f.write_char('F')?,
+                    None => f.write_char(' ')?,
+                }
+            }
+            f.write_char('\n')?
+        }
+
+        writeln!(f, "{}", "-".repeat(self.board.len_of(Axis(0))))?;
+
+        for (i, snake) in self.snakes.iter().enumerate() {
+            writeln!(
+                f,
+                "snake {} [{}]: score {} | state {:?}",
+                i,
+                snake_glyph(i, true),
+                self.scores[i],
+                snake.state
+            )?;
+        }
+        writeln!(f, "food expires in: {}", self.food_timer)?;
+
+        Ok(())
+    }
+}
+
+/// converts between this crate's internal `(y, x)` array-index convention and the `(x, y)`
+/// convention used by `ArenaState` and most external tooling
+fn yx_to_xy(pos: (usize, usize)) -> (usize, usize) {
+    (pos.1, pos.0)
+}
+
+/// external, stable representation of a single snake within an `ArenaState`; coordinates use
+/// the `(x, y)` convention, see `ArenaState`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnakeDto {
+    pub head: (usize, usize),
+    /// occupied cells from tail to head
+    pub body: Vec<(usize, usize)>,
+    pub length: u32,
+    pub direction: Option<Direction>,
+    pub state: GameState,
+    pub score: u32,
+}
+
+/// external, stable JSON representation of a `SnakeArena`, meant to back an HTTP game server
+/// (e.g. pairing `suggest_move_json` with a posted state to reply with `{"move": "up"}`).
+///
+/// unlike this crate's internal `(y, x)` array-index convention, coordinates here use the
+/// common `(x, y)` convention (x = column, y = row); `food_pos` and every snake's `head`/`body`
+/// are converted on the way in and out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArenaState {
+    pub width: usize,
+    pub height: usize,
+    pub food_pos: (usize, usize),
+    pub food_timer: u32,
+    pub food_time_budget: u32,
+    pub snakes: Vec<SnakeDto>,
+}
+
+impl<R: Rng> SnakeArena<R> {
+    /// builds the external, serializable `ArenaState` for this arena, converting coordinates
+    /// from the internal `(y, x)` convention to the external `(x, y)` one
+    pub fn to_state(&self) -> ArenaState {
+        let (height, width) = self.board_size();
+        let snakes = self
+            .snakes
+            .iter()
+            .zip(&self.scores)
+            .map(|(snake, &score)| SnakeDto {
+                head: yx_to_xy(snake.pos),
+                body: snake.body.iter().copied().map(yx_to_xy).collect(),
+                length: snake.lvl,
+                direction: snake.dir,
+                state: snake.state,
+                score,
+            })
+            .collect();
+
+        ArenaState {
+            width,
+            height,
+            food_pos: yx_to_xy(self.food_pos),
+            food_timer: self.food_timer,
+            food_time_budget: self.food_time_budget,
+            snakes,
+        }
+    }
+
+    /// serializes this arena's state to JSON, see `to_state` for the schema
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.to_state()).expect("ArenaState always serializes")
+    }
+}
+
+impl SnakeArena<ThreadRng> {
+    /// rebuilds an arena from a previously serialized `ArenaState`, converting coordinates back
+    /// from the external `(x, y)` convention to the internal `(y, x)` one.
+    ///
+    /// `state` may come straight off the wire from an untrusted client, so every coordinate is
+    /// bounds-checked against `width`/`height` before use; a `food_pos` or snake `head`/`body`
+    /// cell outside the board would otherwise panic later when indexing into `board`.
+    fn from_state(state: &ArenaState) -> Result<Self, serde_json::Error> {
+        let in_bounds = |pos: (usize, usize)| pos.0 < state.width && pos.1 < state.height;
+
+        if state.width == 0 || state.height == 0 {
+            return Err(SerdeError::custom("board width and height must be non-zero"));
+        }
+        let board_cells = match state.width.checked_mul(state.height) {
+            Some(cells) if cells <= MAX_BOARD_CELLS => cells,
+            _ => {
+                return Err(SerdeError::custom(format!(
+                    "board is too large, width * height must not exceed {}",
+                    MAX_BOARD_CELLS
+                )))
+            }
+        };
+        if !in_bounds(state.food_pos) {
+            return Err(SerdeError::custom("food_pos is outside the board"));
+        }
+        for snake in &state.snakes {
+            if !in_bounds(snake.head) || snake.body.iter().any(|&pos| !in_bounds(pos)) {
+                return Err(SerdeError::custom("a snake's head or body is outside the board"));
+            }
+            // a snake's body can never legitimately exceed the number of cells on the board;
+            // without this cap a client could post one in-bounds cell repeated millions of
+            // times to inflate parsing/allocation cost
+            if snake.body.len() > board_cells {
+                return Err(SerdeError::custom(
+                    "a snake's body must not exceed the number of cells on the board",
+                ));
+            }
+        }
+
+        let snakes = state
+            .snakes
+            .iter()
+            .map(|dto| Snake {
+                pos: xy_to_yx(dto.head),
+                dir: dto.direction,
+                lvl: dto.length,
+                body: dto.body.iter().copied().map(xy_to_yx).collect(),
+                state: dto.state,
+            })
+            .collect();
+
+        let mut arena = SnakeArena {
+            board: Array2::from_elem((state.height, state.width), None),
+            food_pos: xy_to_yx(state.food_pos),
+            food_timer: state.food_timer,
+            food_time_budget: state.food_time_budget,
+            scores: state.snakes.iter().map(|dto| dto.score).collect(),
+            cycle_index: hamiltonian_cycle(state.width, state.height),
+            snakes,
+            rng: thread_rng(),
+        };
+        arena.sync_board();
+        Ok(arena)
+    }
+
+    /// rebuilds an arena from a JSON `ArenaState`, see `to_json`/`to_state`; returns an error
+    /// for malformed JSON as well as for well-typed but out-of-bounds coordinates
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let state: ArenaState = serde_json::from_str(json)?;
+        SnakeArena::from_state(&state)
+    }
+}
+
+/// converts from the external `(x, y)` convention back to this crate's internal `(y, x)` one
+fn xy_to_yx(pos: (usize, usize)) -> (usize, usize) {
+    (pos.1, pos.0)
+}
+
+#[derive(Serialize)]
+struct MoveResponse {
+    #[serde(rename = "move")]
+    direction: Option<Direction>,
+}
+
+/// one-shot HTTP-handler helper: parses a posted `ArenaState` JSON body, runs the safe-move
+/// autopilot for `snake_idx`, and replies with `{"move": "up"}` (or `{"move": null}` if every
+/// direction is immediately fatal). Errors on malformed JSON, out-of-bounds coordinates, or a
+/// `snake_idx` that doesn't name one of the posted snakes.
+pub fn suggest_move_json(json: &str, snake_idx: usize) -> serde_json::Result<String> {
+    let arena = SnakeArena::from_json(json)?;
+    if snake_idx >= arena.snakes.len() {
+        return Err(SerdeError::custom(format!(
+            "snake_idx {} is out of bounds, state has {} snake(s)",
+            snake_idx,
+            arena.snakes.len()
+        )));
+    }
+    let direction = arena.suggest_direction(snake_idx);
+    serde_json::to_string(&MoveResponse { direction })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// builds an arena directly from explicit snakes, bypassing random spawn/food placement
+    fn test_arena(snakes: Vec<Snake>, food_pos: (usize, usize), board_size: (usize, usize)) -> SnakeArena<ThreadRng> {
+        test_arena_with_cycle(snakes, food_pos, board_size, None)
+    }
+
+    /// like `test_arena`, but also lets a test supply a precomputed Hamiltonian cycle
+    fn test_arena_with_cycle(
+        snakes: Vec<Snake>,
+        food_pos: (usize, usize),
+        board_size: (usize, usize),
+        cycle_index: Option<Array2<usize>>,
+    ) -> SnakeArena<ThreadRng> {
+        let mut arena = SnakeArena {
+            board: Array2::from_elem(board_size, None),
+            food_pos,
+            food_timer: 100,
+            food_time_budget: 100,
+            scores: vec![0; snakes.len()],
+            cycle_index,
+            snakes,
+            rng: thread_rng(),
+        };
+        arena.sync_board();
+        arena
+    }
+
+    #[test]
+    fn spawn_positions_are_unique_on_a_small_board_with_many_snakes() {
+        let positions = spawn_positions((3, 3), 5);
+
+        assert_eq!(positions.len(), 5);
+        assert_eq!(positions.iter().collect::<HashSet<_>>().len(), 5);
+    }
+
+    #[test]
+    fn hamiltonian_cycle_visits_every_cell_exactly_once_on_even_height_board() {
+        let cycle = hamiltonian_cycle(3, 4).expect("even height admits a cycle");
+
+        let mut indices: Vec<usize> = cycle.iter().copied().collect();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn hamiltonian_cycle_visits_every_cell_exactly_once_on_even_width_odd_height_board() {
+        let cycle = hamiltonian_cycle(4, 3).expect("even width admits a cycle");
+
+        let mut indices: Vec<usize> = cycle.iter().copied().collect();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..12).collect::<Vec<_>>());
+    }
+
+    /// walks `hamiltonian_next` for a full lap of a `width` x `height` board and checks it
+    /// visits every cell exactly once before closing back up to where it started
+    fn assert_hamiltonian_next_walks_the_whole_cycle(width: usize, height: usize) {
+        let mut snake = Snake::new((0, 0), 1);
+        snake.body.push_back((0, 0));
+        let mut arena = test_arena_with_cycle(vec![snake], (1, 1), (height, width), hamiltonian_cycle(width, height));
+
+        let start = arena.snakes[0].pos;
+        let mut visited = HashSet::new();
+        visited.insert(start);
+
+        for step in 0..(width * height) {
+            let dir = arena.hamiltonian_next(0).expect("board admits a cycle");
+            let pos = wrapped_step(arena.board_size(), arena.snakes[0].pos, dir);
+
+            if step < width * height - 1 {
+                assert!(visited.insert(pos), "revisited {:?} at step {}", pos, step);
+            } else {
+                assert_eq!(pos, start, "the cycle should close back up to the start");
+            }
+
+            arena.snakes[0].pos = pos;
+        }
+    }
+
+    #[test]
+    fn hamiltonian_next_walks_the_whole_cycle_without_revisiting_early_even_width() {
+        // neither dimension is even-and-primary here in the sense of `hamiltonian_cycle_even_height`:
+        // width is even, height is odd, so this exercises the transposed (even-width) branch
+        assert_hamiltonian_next_walks_the_whole_cycle(4, 3);
+    }
+
+    #[test]
+    fn hamiltonian_next_walks_the_whole_cycle_without_revisiting_early_even_height() {
+        // height is even, so this exercises `hamiltonian_cycle_even_height`'s direct
+        // construction rather than the transposed branch above
+        assert_hamiltonian_next_walks_the_whole_cycle(3, 4);
+    }
+
+    #[test]
+    fn hamiltonian_next_with_shortcut_never_self_collides_over_many_laps() {
+        let (width, height) = (6, 6);
+        let cycle_index = hamiltonian_cycle(width, height).expect("even height admits a cycle");
+        let cycle_len = width * height;
+
+        // cycle index -> position, so food can be dropped at a chosen distance ahead of the head
+        let mut pos_of_index = vec![(0, 0); cycle_len];
+        for y in 0..height {
+            for x in 0..width {
+                pos_of_index[cycle_index[(y, x)]] = (y, x);
+            }
+        }
+
+        let mut snake = Snake::new((0, 0), 4);
+        snake.body.push_back((0, 0));
+        let mut arena = test_arena_with_cycle(vec![snake], (0, 0), (height, width), Some(cycle_index.clone()));
+
+        for step in 0..(cycle_len * 50) {
+            // keep dragging the food a little further ahead of the head each step (wrapping
+            // around the cycle), so a shortcut is worth taking most of the time
+            let head_index = cycle_index[arena.snakes[0].pos];
+            arena.food_pos = pos_of_index[(head_index + 1 + step * 7) % cycle_len];
+
+            let dir = arena
+                .hamiltonian_next_with_shortcut(0)
+                .expect("a single snake on a board that admits a cycle always has a safe move");
+            let pos = wrapped_step(arena.board_size(), arena.snakes[0].pos, dir);
+
+            assert!(
+                !arena.snakes[0].body.iter().any(|&seg| seg == pos),
+                "self-collision at step {}: stepped onto own body at {:?}",
+                step,
+                pos,
+            );
+
+            arena.snakes[0].body.push_back(pos);
+            if arena.snakes[0].body.len() > arena.snakes[0].lvl as usize {
+                arena.snakes[0].body.pop_front();
+            }
+            arena.snakes[0].pos = pos;
+        }
+    }
+
+    #[test]
+    fn suggest_direction_allows_stepping_into_own_vacating_tail() {
+        // a snake filling every cell of a 1x3 board: the only way to not immediately die is to
+        // step into the tail cell it is about to vacate
+        let mut snake = Snake::new((0, 2), 3);
+        snake.body = VecDeque::from(vec![(0, 0), (0, 1), (0, 2)]);
+        let arena = test_arena(vec![snake], (0, 0), (1, 3));
+
+        assert_eq!(arena.suggest_direction(0), Some(Direction::RIGHT));
+    }
+
+    #[test]
+    fn tick_all_head_to_head_equal_length_both_die() {
+        let mut a = Snake::new((2, 2), 1);
+        a.body.push_back((2, 2));
+        let mut b = Snake::new((2, 4), 1);
+        b.body.push_back((2, 4));
+        let mut arena = test_arena(vec![a, b], (0, 0), (5, 5));
+
+        let states = arena.tick_all(&[Some(Direction::RIGHT), Some(Direction::LEFT)]);
+
+        assert_eq!(states, vec![GameState::GameOver, GameState::GameOver]);
+    }
+
+    #[test]
+    fn tick_all_self_collision_ends_in_game_over() {
+        let mut snake = Snake::new((0, 1), 3);
+        // a U-shape: moving UP steps the head straight into its own body
+        snake.body = VecDeque::from(vec![(0, 0), (1, 0), (1, 1), (0, 1)]);
+        let mut arena = test_arena(vec![snake], (2, 2), (2, 2));
+
+        let states = arena.tick_all(&[Some(Direction::UP)]);
+
+        assert_eq!(states, vec![GameState::GameOver]);
+    }
+
+    #[test]
+    fn tick_all_filling_the_board_ends_in_won_without_hanging_on_food_respawn() {
+        // 1x3 board, snake occupies (0,0) and (0,1), food at the only free cell (0,2): eating
+        // it fills the board, so respawning food must not loop forever looking for a free cell
+        let mut snake = Snake::new((0, 1), 2);
+        snake.body = VecDeque::from(vec![(0, 0), (0, 1)]);
+        let mut arena = test_arena(vec![snake], (0, 2), (1, 3));
+
+        let states = arena.tick_all(&[Some(Direction::RIGHT)]);
+
+        assert_eq!(states, vec![GameState::Won]);
+    }
+
+    #[test]
+    fn tick_all_food_timer_counts_down_and_relocates_without_reward_on_expiry() {
+        let mut snake = Snake::new((0, 0), 1);
+        snake.body.push_back((0, 0));
+        let mut arena = SnakeArena {
+            board: Array2::from_elem((3, 3), None),
+            food_pos: (2, 2),
+            food_timer: 2,
+            food_time_budget: 2,
+            scores: vec![0],
+            cycle_index: None,
+            snakes: vec![snake],
+            rng: thread_rng(),
+        };
+        arena.sync_board();
+
+        arena.tick_all(&[None]);
+        assert_eq!(arena.food_timer, 1);
+        assert_eq!(arena.food_pos, (2, 2));
+
+        arena.tick_all(&[None]);
+        // the timer expired: food relocates for no reward and resets to the full budget
+        assert_eq!(arena.food_timer, arena.food_time_budget);
+        assert_eq!(arena.scores[0], 0);
+    }
+
+    #[test]
+    fn tick_all_banks_remaining_food_timer_as_score_on_eating() {
+        let mut snake = Snake::new((0, 0), 1);
+        snake.body.push_back((0, 0));
+        let mut arena = SnakeArena {
+            board: Array2::from_elem((3, 3), None),
+            food_pos: (0, 1), // one step right of the head
+            food_timer: 7,
+            food_time_budget: 20,
+            scores: vec![0],
+            cycle_index: None,
+            snakes: vec![snake],
+            rng: thread_rng(),
+        };
+        arena.sync_board();
+
+        let timer_before_move = arena.food_timer - 1; // food_timer decrements before movement resolves
+        let states = arena.tick_all(&[Some(Direction::RIGHT)]);
+
+        assert_eq!(states, vec![GameState::Running]);
+        assert_eq!(arena.scores[0], timer_before_move);
+        assert_eq!(arena.snakes[0].lvl, 2);
+        assert_eq!(arena.food_timer, arena.food_time_budget);
+    }
+
+    #[test]
+    fn to_json_from_json_round_trip() {
+        let arena = SnakeArena::new(7, 7, &[3], 20);
+        let json = arena.to_json();
+
+        let restored = SnakeArena::from_json(&json).expect("a freshly serialized state is valid");
+
+        assert_eq!(restored.to_json(), json);
+    }
+
+    #[test]
+    fn from_json_rejects_out_of_bounds_coordinates() {
+        let json = r#"{"width":5,"height":5,"food_pos":[0,0],"food_timer":5,
+            "food_time_budget":20,"snakes":[{"head":[99,99],"body":[[99,99]],
+            "length":1,"direction":null,"state":"running","score":0}]}"#;
+
+        assert!(SnakeArena::<ThreadRng>::from_json(json).is_err());
+    }
+
+    #[test]
+    fn suggest_move_json_rejects_out_of_range_snake_idx() {
+        let arena = SnakeArena::new(5, 5, &[1], 20);
+        let json = arena.to_json();
+
+        assert!(suggest_move_json(&json, arena.snakes.len()).is_err());
+    }
+}